@@ -20,6 +20,9 @@ extern crate glutin;
 #[cfg(feature = "headless")]
 pub use headless::{init_headless, init_headless_raw};
 
+#[cfg(feature = "surface")]
+pub use surface::{init_existing_surface, init_surface, update_views as update_views_surface};
+
 use core::memory::Typed;
 use core::{format, handle, texture};
 use device_gl::Resources as R;
@@ -28,6 +31,9 @@ use glutin::{CreationError, NotCurrent, PossiblyCurrent};
 #[cfg(feature = "headless")]
 mod headless;
 
+#[cfg(feature = "surface")]
+pub mod surface;
+
 /// Initialize with a window builder.
 /// Generically parametrized version over the main framebuffer format.
 ///
@@ -70,21 +76,15 @@ where
     Cf: format::RenderFormat,
     Df: format::DepthFormat,
 {
-    let (window, device, factory, color_view, ds_view) = init_raw(
-        window,
-        context,
-        event_loop,
-        Cf::get_format(),
-        Df::get_format(),
-    )?;
-
-    Ok((
+    let Init {
         window,
         device,
         factory,
-        Typed::new(color_view),
-        Typed::new(ds_view),
-    ))
+        color_view,
+        depth_view,
+    } = Init::new(window, context, event_loop)?;
+
+    Ok((window, device, factory, color_view, depth_view))
 }
 
 /// Initialize with an existing Glutin window.
@@ -120,28 +120,200 @@ where
     Cf: format::RenderFormat,
     Df: format::DepthFormat,
 {
-    let (window, device, factory, color_view, ds_view) =
-        init_existing_raw(window, Cf::get_format(), Df::get_format());
-    (
+    let Init {
         window,
         device,
         factory,
-        Typed::new(color_view),
-        Typed::new(ds_view),
-    )
+        color_view,
+        depth_view,
+    } = Init::from_existing(window);
+
+    (window, device, factory, color_view, depth_view)
+}
+
+/// The result of initializing gfx with a Glutin window, generically
+/// parametrized over the main framebuffer format.
+///
+/// Returned by [`Init::new`] and [`Init::from_existing`]. Destructure it to
+/// pick out just the fields you need, e.g.
+/// `let Init { window, device, factory, .. } = Init::new(...)?;`.
+pub struct Init<Cf, Df> {
+    /// The windowed context, now current.
+    pub window: glutin::WindowedContext<PossiblyCurrent>,
+    /// The OpenGL device.
+    pub device: device_gl::Device,
+    /// The OpenGL factory.
+    pub factory: device_gl::Factory,
+    /// The main color target.
+    pub color_view: handle::RenderTargetView<R, Cf>,
+    /// The main depth/stencil target.
+    pub depth_view: handle::DepthStencilView<R, Df>,
+}
+
+impl<Cf, Df> Init<Cf, Df>
+where
+    Cf: format::RenderFormat,
+    Df: format::DepthFormat,
+{
+    /// Initialize with a window builder. Generically parametrized version
+    /// over the main framebuffer format.
+    pub fn new<T>(
+        window: glutin::window::WindowBuilder,
+        context: glutin::ContextBuilder<NotCurrent>,
+        event_loop: &glutin::event_loop::EventLoop<T>,
+    ) -> Result<Self, CreationError> {
+        let RawInit {
+            window,
+            device,
+            factory,
+            color_view,
+            depth_view,
+        } = RawInit::new(
+            window,
+            context,
+            event_loop,
+            Cf::get_format(),
+            Df::get_format(),
+        )?;
+
+        Ok(Init {
+            window,
+            device,
+            factory,
+            color_view: Typed::new(color_view),
+            depth_view: Typed::new(depth_view),
+        })
+    }
+
+    /// Initialize with a window builder and additional render-setup config
+    /// (vsync, multisampling, sRGB). Generically parametrized version over
+    /// the main framebuffer format.
+    pub fn with_config<T>(
+        window: glutin::window::WindowBuilder,
+        context: glutin::ContextBuilder<NotCurrent>,
+        config: GfxConfig,
+        event_loop: &glutin::event_loop::EventLoop<T>,
+    ) -> Result<Self, CreationError> {
+        let RawInit {
+            window,
+            device,
+            factory,
+            color_view,
+            depth_view,
+        } = RawInit::with_config(
+            window,
+            context,
+            config,
+            event_loop,
+            Cf::get_format(),
+            Df::get_format(),
+        )?;
+
+        Ok(Init {
+            window,
+            device,
+            factory,
+            color_view: Typed::new(color_view),
+            depth_view: Typed::new(depth_view),
+        })
+    }
+
+    /// Initialize with an existing Glutin window. Generically parametrized
+    /// version over the main framebuffer format.
+    pub fn from_existing(window: glutin::WindowedContext<NotCurrent>) -> Self {
+        let RawInit {
+            window,
+            device,
+            factory,
+            color_view,
+            depth_view,
+        } = RawInit::from_existing(window, Cf::get_format(), Df::get_format());
+
+        Init {
+            window,
+            device,
+            factory,
+            color_view: Typed::new(color_view),
+            depth_view: Typed::new(depth_view),
+        }
+    }
 }
 
-fn get_window_dimensions(ctx: &glutin::WindowedContext<PossiblyCurrent>) -> texture::Dimensions {
+/// `requested_samples`, when `Some`, is used as the main targets' sample
+/// count instead of reading back `ctx`'s actual pixel format: a driver can
+/// silently grant fewer samples (or none) than requested, and a caller
+/// that asked for a specific count should get main targets built to match
+/// its request rather than whatever the driver settled on.
+fn get_window_dimensions(
+    ctx: &glutin::WindowedContext<PossiblyCurrent>,
+    requested_samples: Option<texture::NumSamples>,
+) -> texture::Dimensions {
     let window = ctx.window();
     let (width, height) = {
-        let size = window.inner_size().to_physical(window.hidpi_factor());
+        let size = window.inner_size();
         (size.width as _, size.height as _)
     };
-    let aa = ctx.get_pixel_format().multisampling.unwrap_or(0) as texture::NumSamples;
+    let aa = requested_samples
+        .unwrap_or_else(|| ctx.get_pixel_format().multisampling.unwrap_or(0) as texture::NumSamples);
 
     (width, height, 1, aa.into())
 }
 
+/// Apply the pixel format, depth/stencil buffer sizes, and sRGB setting
+/// required to support the given color and depth/stencil formats to a
+/// `ContextBuilder`.
+fn apply_gfx_color_depth<T: glutin::ContextCurrentState>(
+    context: glutin::ContextBuilder<T>,
+    color_format: format::Format,
+    ds_format: format::Format,
+) -> glutin::ContextBuilder<T> {
+    let color_total_bits = color_format.0.get_total_bits();
+    let alpha_bits = color_format.0.get_alpha_stencil_bits();
+    let depth_total_bits = ds_format.0.get_total_bits();
+    let stencil_bits = ds_format.0.get_alpha_stencil_bits();
+
+    context
+        .with_depth_buffer(depth_total_bits - stencil_bits)
+        .with_stencil_buffer(stencil_bits)
+        .with_pixel_format(color_total_bits - alpha_bits, alpha_bits)
+        .with_srgb(color_format.1 == format::ChannelType::Srgb)
+}
+
+/// Render-setup toggles for building a GL context for gfx: vsync,
+/// multisampling, and sRGB, kept in one place instead of being threaded
+/// through individually.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GfxConfig {
+    /// Whether to request vsync.
+    pub vsync: bool,
+    /// The number of samples to request for multisampled main targets, if
+    /// any.
+    pub multisampling: Option<texture::NumSamples>,
+    /// Whether to request an sRGB-capable pixel format, overriding the
+    /// sRGB-ness implied by the main color format. `None` (the default)
+    /// inherits whatever `apply_gfx_color_depth` already set from the
+    /// color format, so a default `GfxConfig` never changes sRGB behavior
+    /// versus `Init::new`/`RawInit::new`.
+    pub srgb: Option<bool>,
+}
+
+impl GfxConfig {
+    fn apply<T: glutin::ContextCurrentState>(
+        self,
+        context: glutin::ContextBuilder<T>,
+    ) -> glutin::ContextBuilder<T> {
+        let context = context.with_vsync(self.vsync);
+        let context = match self.srgb {
+            Some(srgb) => context.with_srgb(srgb),
+            None => context,
+        };
+        match self.multisampling {
+            Some(samples) => context.with_multisampling(samples as u16),
+            None => context,
+        }
+    }
+}
+
 /// Initialize with a window builder. Raw version.
 pub fn init_raw<T>(
     window: glutin::window::WindowBuilder,
@@ -159,24 +331,15 @@ pub fn init_raw<T>(
     ),
     CreationError,
 > {
-    let window = {
-        let color_total_bits = color_format.0.get_total_bits();
-        let alpha_bits = color_format.0.get_alpha_stencil_bits();
-        let depth_total_bits = ds_format.0.get_total_bits();
-        let stencil_bits = ds_format.0.get_alpha_stencil_bits();
-
-        context
-            .with_depth_buffer(depth_total_bits - stencil_bits)
-            .with_stencil_buffer(stencil_bits)
-            .with_pixel_format(color_total_bits - alpha_bits, alpha_bits)
-            .with_srgb(color_format.1 == format::ChannelType::Srgb)
-            .build_windowed(window, event_loop)?
-    };
-
-    let (window, device, factory, color_view, ds_view) =
-        init_existing_raw(window, color_format, ds_format);
+    let RawInit {
+        window,
+        device,
+        factory,
+        color_view,
+        depth_view,
+    } = RawInit::new(window, context, event_loop, color_format, ds_format)?;
 
-    Ok((window, device, factory, color_view, ds_view))
+    Ok((window, device, factory, color_view, depth_view))
 }
 
 /// Initialize with an existing Glutin window. Raw version.
@@ -191,17 +354,100 @@ pub fn init_existing_raw(
     handle::RawRenderTargetView<R>,
     handle::RawDepthStencilView<R>,
 ) {
-    let window = unsafe { window.make_current().unwrap() };
-    let (device, factory) =
-        device_gl::create(|s| window.get_proc_address(s) as *const std::os::raw::c_void);
+    let RawInit {
+        window,
+        device,
+        factory,
+        color_view,
+        depth_view,
+    } = RawInit::from_existing(window, color_format, ds_format);
+
+    (window, device, factory, color_view, depth_view)
+}
+
+/// Raw version of [`Init`], with untyped main target views.
+pub struct RawInit {
+    /// The windowed context, now current.
+    pub window: glutin::WindowedContext<PossiblyCurrent>,
+    /// The OpenGL device.
+    pub device: device_gl::Device,
+    /// The OpenGL factory.
+    pub factory: device_gl::Factory,
+    /// The main color target.
+    pub color_view: handle::RawRenderTargetView<R>,
+    /// The main depth/stencil target.
+    pub depth_view: handle::RawDepthStencilView<R>,
+}
+
+impl RawInit {
+    /// Initialize with a window builder. Raw version.
+    pub fn new<T>(
+        window: glutin::window::WindowBuilder,
+        context: glutin::ContextBuilder<NotCurrent>,
+        event_loop: &glutin::event_loop::EventLoop<T>,
+        color_format: format::Format,
+        ds_format: format::Format,
+    ) -> Result<Self, CreationError> {
+        let window = apply_gfx_color_depth(context, color_format, ds_format)
+            .build_windowed(window, event_loop)?;
+
+        Ok(Self::from_existing(window, color_format, ds_format))
+    }
+
+    /// Initialize with a window builder and additional render-setup config
+    /// (vsync, multisampling, sRGB). Raw version.
+    pub fn with_config<T>(
+        window: glutin::window::WindowBuilder,
+        context: glutin::ContextBuilder<NotCurrent>,
+        config: GfxConfig,
+        event_loop: &glutin::event_loop::EventLoop<T>,
+        color_format: format::Format,
+        ds_format: format::Format,
+    ) -> Result<Self, CreationError> {
+        let requested_samples = config.multisampling;
+        let context = config.apply(apply_gfx_color_depth(context, color_format, ds_format));
+        let window = context.build_windowed(window, event_loop)?;
+
+        Ok(Self::from_existing_impl(
+            window,
+            color_format,
+            ds_format,
+            requested_samples,
+        ))
+    }
+
+    /// Initialize with an existing Glutin window. Raw version.
+    pub fn from_existing(
+        window: glutin::WindowedContext<NotCurrent>,
+        color_format: format::Format,
+        ds_format: format::Format,
+    ) -> Self {
+        Self::from_existing_impl(window, color_format, ds_format, None)
+    }
 
-    // create the main color/depth targets
-    let dim = get_window_dimensions(&window);
-    let (color_view, ds_view) =
-        device_gl::create_main_targets_raw(dim, color_format.0, ds_format.0);
+    fn from_existing_impl(
+        window: glutin::WindowedContext<NotCurrent>,
+        color_format: format::Format,
+        ds_format: format::Format,
+        requested_samples: Option<texture::NumSamples>,
+    ) -> Self {
+        let window = unsafe { window.make_current().unwrap() };
+        let (device, factory) =
+            device_gl::create(|s| window.get_proc_address(s) as *const std::os::raw::c_void);
 
-    // done
-    (window, device, factory, color_view, ds_view)
+        // create the main color/depth targets
+        let dim = get_window_dimensions(&window, requested_samples);
+        let (color_view, depth_view) =
+            device_gl::create_main_targets_raw(dim, color_format.0, ds_format.0);
+
+        RawInit {
+            window,
+            device,
+            factory,
+            color_view,
+            depth_view,
+        }
+    }
 }
 
 /// Update the internal dimensions of the main framebuffer targets. Generic version over the format.
@@ -231,7 +477,7 @@ pub fn update_views_raw(
     handle::RawRenderTargetView<R>,
     handle::RawDepthStencilView<R>,
 )> {
-    let dim = get_window_dimensions(window);
+    let dim = get_window_dimensions(window, None);
     if dim != old_dimensions {
         Some(device_gl::create_main_targets_raw(
             dim,
@@ -255,8 +501,172 @@ where
     Cf: format::RenderFormat,
     Df: format::DepthFormat,
 {
-    let dim = get_window_dimensions(window);
+    let dim = get_window_dimensions(window, None);
     let (color_view_raw, depth_view_raw) =
         device_gl::create_main_targets_raw(dim, Cf::get_format().0, Df::get_format().0);
     (Typed::new(color_view_raw), Typed::new(depth_view_raw))
 }
+
+/// Owns the main color/depth targets along with the physical window size
+/// and HiDPI factor they were built for, so [`on_resize`](Self::on_resize)
+/// can tell a genuine resize from a spurious one (e.g. an event that
+/// didn't actually change either) and skip reallocating the targets when
+/// nothing changed.
+pub struct TargetTracker<Cf, Df> {
+    color_view: handle::RenderTargetView<R, Cf>,
+    depth_view: handle::DepthStencilView<R, Df>,
+    cached_size: glutin::dpi::PhysicalSize<u32>,
+    cached_hidpi_factor: f64,
+}
+
+impl<Cf, Df> TargetTracker<Cf, Df>
+where
+    Cf: format::RenderFormat,
+    Df: format::DepthFormat,
+{
+    /// Start tracking `color_view`/`depth_view`, caching `ctx`'s current
+    /// physical size and HiDPI factor.
+    pub fn new(
+        ctx: &glutin::WindowedContext<PossiblyCurrent>,
+        color_view: handle::RenderTargetView<R, Cf>,
+        depth_view: handle::DepthStencilView<R, Df>,
+    ) -> Self {
+        let window = ctx.window();
+        TargetTracker {
+            color_view,
+            depth_view,
+            cached_size: window.inner_size(),
+            cached_hidpi_factor: window.scale_factor(),
+        }
+    }
+
+    /// The current main color target.
+    pub fn color_view(&self) -> &handle::RenderTargetView<R, Cf> {
+        &self.color_view
+    }
+
+    /// The current main depth/stencil target.
+    pub fn depth_view(&self) -> &handle::DepthStencilView<R, Df> {
+        &self.depth_view
+    }
+
+    /// Recreate the main targets if `ctx`'s logical size or HiDPI factor
+    /// have changed since the last call (or since `new`). Best called just
+    /// after a `WindowResize` event. Returns whether the targets were
+    /// replaced.
+    pub fn on_resize(&mut self, ctx: &glutin::WindowedContext<PossiblyCurrent>) -> bool {
+        let window = ctx.window();
+        let size = window.inner_size();
+        let hidpi_factor = window.scale_factor();
+
+        if size == self.cached_size && hidpi_factor == self.cached_hidpi_factor {
+            return false;
+        }
+        self.cached_size = size;
+        self.cached_hidpi_factor = hidpi_factor;
+
+        let (color_view, depth_view) = new_views(ctx);
+        self.color_view = color_view;
+        self.depth_view = depth_view;
+        true
+    }
+}
+
+/// Extension methods on `glutin::ContextBuilder` for fluent gfx setup.
+pub trait ContextBuilderExt<T: glutin::ContextCurrentState> {
+    /// Request the pixel format, depth/stencil buffers, and sRGB setting
+    /// needed to support the given main color and depth/stencil formats.
+    fn with_gfx_color_depth<Cf, Df>(self) -> Self
+    where
+        Cf: format::RenderFormat,
+        Df: format::DepthFormat;
+}
+
+impl<T: glutin::ContextCurrentState> ContextBuilderExt<T> for glutin::ContextBuilder<'_, T> {
+    fn with_gfx_color_depth<Cf, Df>(self) -> Self
+    where
+        Cf: format::RenderFormat,
+        Df: format::DepthFormat,
+    {
+        apply_gfx_color_depth(self, Cf::get_format(), Df::get_format())
+    }
+}
+
+/// Extension method on a freshly built `glutin::WindowedContext` for
+/// fluent gfx setup.
+///
+/// The resize-time counterpart, `update_gfx`, lives on the separate
+/// [`UpdateGfxExt`] trait rather than here: `init_gfx` consumes a
+/// `WindowedContext<NotCurrent>` to make it current, while `update_gfx`
+/// only makes sense on an already-current `WindowedContext<PossiblyCurrent>`
+/// and takes `&self`. A single trait can't give those two impls different
+/// method sets, so the resize-time method is split out instead of being
+/// a no-op stub on the `NotCurrent` impl.
+pub trait WindowedContextExt {
+    /// Make the context current and create a `Device`, `Factory`, and main
+    /// color/depth targets for it. Generic version over the main
+    /// framebuffer format.
+    fn init_gfx<Cf, Df>(
+        self,
+    ) -> (
+        glutin::WindowedContext<PossiblyCurrent>,
+        device_gl::Device,
+        device_gl::Factory,
+        handle::RenderTargetView<R, Cf>,
+        handle::DepthStencilView<R, Df>,
+    )
+    where
+        Cf: format::RenderFormat,
+        Df: format::DepthFormat;
+}
+
+impl WindowedContextExt for glutin::WindowedContext<NotCurrent> {
+    fn init_gfx<Cf, Df>(
+        self,
+    ) -> (
+        glutin::WindowedContext<PossiblyCurrent>,
+        device_gl::Device,
+        device_gl::Factory,
+        handle::RenderTargetView<R, Cf>,
+        handle::DepthStencilView<R, Df>,
+    )
+    where
+        Cf: format::RenderFormat,
+        Df: format::DepthFormat,
+    {
+        init_existing::<Cf, Df>(self)
+    }
+}
+
+/// Extension method on a current `glutin::WindowedContext` to update its
+/// main gfx targets in place after a resize.
+///
+/// Kept separate from [`WindowedContextExt`] because `update_gfx` requires
+/// an already-current context (`&self`) while `WindowedContextExt::init_gfx`
+/// consumes a not-yet-current one (`self`) to make it current; see that
+/// trait's docs for the full rationale.
+pub trait UpdateGfxExt {
+    /// Update the main color/depth targets in place if the window
+    /// resolution has changed. Generic version over the main framebuffer
+    /// format.
+    fn update_gfx<Cf, Df>(
+        &self,
+        color_view: &mut handle::RenderTargetView<R, Cf>,
+        ds_view: &mut handle::DepthStencilView<R, Df>,
+    ) where
+        Cf: format::RenderFormat,
+        Df: format::DepthFormat;
+}
+
+impl UpdateGfxExt for glutin::WindowedContext<PossiblyCurrent> {
+    fn update_gfx<Cf, Df>(
+        &self,
+        color_view: &mut handle::RenderTargetView<R, Cf>,
+        ds_view: &mut handle::DepthStencilView<R, Df>,
+    ) where
+        Cf: format::RenderFormat,
+        Df: format::DepthFormat,
+    {
+        update_views(self, color_view, ds_view)
+    }
+}
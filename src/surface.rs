@@ -0,0 +1,158 @@
+// Copyright 2015 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for glutin's decoupled `Display`/`Config`/`Context`/`Surface`
+//! model, used by newer glutin versions and any windowing backend that
+//! hands us a `raw-window-handle` rather than a `glutin::WindowedContext`.
+
+use std::num::NonZeroU32;
+
+// The rest of this crate targets `glutin` 0.22's `WindowedContext` API; the
+// `Display`/`Config`/`Context`/`Surface` split this module needs only
+// exists in `glutin` 0.30+. The two can't be unified under one dependency,
+// so Cargo.toml pulls in 0.30 as a second, differently-named package
+// (`glutin030`), aliased to `glutin` here so the rest of this file can
+// refer to it the same way the rest of the crate refers to its `glutin`.
+use glutin030 as glutin;
+
+use glutin::context::{NotCurrentContext, PossiblyCurrentContext};
+use glutin::display::GetGlDisplay;
+use glutin::prelude::*;
+use glutin::surface::{GlSurface, Surface, WindowSurface};
+
+use core::memory::Typed;
+use core::{format, handle, texture};
+use device_gl::Resources as R;
+
+fn get_surface_dimensions(surface: &Surface<WindowSurface>) -> texture::Dimensions {
+    let width = surface.width().unwrap_or(1).max(1);
+    let height = surface.height().unwrap_or(1).max(1);
+    (width as _, height as _, 1, texture::AaMode::Single)
+}
+
+/// Make `context` current on `surface` and create a `Device`, `Factory`,
+/// and main color/depth targets for it. Generically parametrized version
+/// over the main framebuffer format.
+pub fn init_surface<Cf, Df>(
+    context: NotCurrentContext,
+    surface: &Surface<WindowSurface>,
+) -> (
+    PossiblyCurrentContext,
+    device_gl::Device,
+    device_gl::Factory,
+    handle::RenderTargetView<R, Cf>,
+    handle::DepthStencilView<R, Df>,
+)
+where
+    Cf: format::RenderFormat,
+    Df: format::DepthFormat,
+{
+    let (context, device, factory, color_view, ds_view) =
+        init_existing_surface(context, surface, Cf::get_format(), Df::get_format());
+    (
+        context,
+        device,
+        factory,
+        Typed::new(color_view),
+        Typed::new(ds_view),
+    )
+}
+
+/// Make `context` current on `surface` and create a `Device`, `Factory`,
+/// and main color/depth targets for it. Raw version.
+pub fn init_existing_surface(
+    context: NotCurrentContext,
+    surface: &Surface<WindowSurface>,
+    color_format: format::Format,
+    ds_format: format::Format,
+) -> (
+    PossiblyCurrentContext,
+    device_gl::Device,
+    device_gl::Factory,
+    handle::RawRenderTargetView<R>,
+    handle::RawDepthStencilView<R>,
+) {
+    let context = context
+        .make_current(surface)
+        .expect("failed to make context current on surface");
+
+    let (device, factory) = device_gl::create(|s| {
+        let s = std::ffi::CString::new(s).unwrap();
+        context.display().get_proc_address(s.as_c_str()) as *const std::os::raw::c_void
+    });
+
+    // create the main color/depth targets
+    let dim = get_surface_dimensions(surface);
+    let (color_view, ds_view) =
+        device_gl::create_main_targets_raw(dim, color_format.0, ds_format.0);
+
+    // done
+    (context, device, factory, color_view, ds_view)
+}
+
+/// Update the internal dimensions of the main framebuffer targets to the
+/// size the GL surface reports. Generic version over the format.
+pub fn update_views<Cf, Df>(
+    context: &PossiblyCurrentContext,
+    surface: &Surface<WindowSurface>,
+    color_view: &mut handle::RenderTargetView<R, Cf>,
+    ds_view: &mut handle::DepthStencilView<R, Df>,
+) where
+    Cf: format::RenderFormat,
+    Df: format::DepthFormat,
+{
+    let dim = color_view.get_dimensions();
+    assert_eq!(dim, ds_view.get_dimensions());
+    if let Some((cv, dv)) =
+        update_views_raw(context, surface, dim, Cf::get_format(), Df::get_format())
+    {
+        *color_view = Typed::new(cv);
+        *ds_view = Typed::new(dv);
+    }
+}
+
+/// Return new main target views if the size the GL surface reports has
+/// changed from the old dimensions, calling `resize` on the surface with
+/// that same size first. The size is read from the surface alone; no
+/// window size is consulted, so a surface whose reported size does not
+/// follow its window is not resized to the window.
+pub fn update_views_raw(
+    context: &PossiblyCurrentContext,
+    surface: &Surface<WindowSurface>,
+    old_dimensions: texture::Dimensions,
+    color_format: format::Format,
+    ds_format: format::Format,
+) -> Option<(
+    handle::RawRenderTargetView<R>,
+    handle::RawDepthStencilView<R>,
+)> {
+    let dim = get_surface_dimensions(surface);
+    if dim == old_dimensions {
+        return None;
+    }
+
+    let (width, height, ..) = dim;
+    if let (Some(width), Some(height)) = (
+        NonZeroU32::new(width as u32),
+        NonZeroU32::new(height as u32),
+    ) {
+        surface.resize(context, width, height);
+    }
+
+    Some(device_gl::create_main_targets_raw(
+        dim,
+        color_format.0,
+        ds_format.0,
+    ))
+}